@@ -1,8 +1,12 @@
 use clap::Parser;
 use colored::*;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
 use walkdir::WalkDir;
 
 
@@ -20,14 +24,27 @@ struct Cli {
     format: OutputFormat,
 
     #[arg(short, long)]
-    ignore: Vec<String>
+    ignore: Vec<String>,
+
+    #[arg(short, long)]
+    jobs: Option<usize>,
+
+    #[arg(short, long)]
+    config: Option<String>,
+
+    #[arg(long)]
+    no_default_rules: bool,
+
+    #[arg(long)]
+    report_unused_suppressions: bool,
 }
 
 
 #[derive(clap::ValueEnum, Clone, Debug)]
 enum OutputFormat {
     Text,
-    Compact
+    Compact,
+    Sarif,
 }
 
 #[derive(Debug, Clone)]
@@ -40,7 +57,8 @@ struct AnalysisResult {
     code_snippet: String
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum Severity {
     Error,
     Warning,
@@ -56,6 +74,15 @@ impl Severity {
             Severity::Warning => "WARNING".yellow(),
         }
     }
+
+    // Уровень в терминах SARIF 2.1.0
+    fn sarif_level(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "note",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -81,12 +108,64 @@ impl TextRule {
             pattern: Regex::new(pattern).unwrap(), 
             message: message.to_string(), 
             severity, 
-            languages: languge.iter().map(|s| s.to_string()).collect(), 
+            languages: languge.iter().map(|s| s.to_string()).collect(),
         }
     }
 }
 
 
+// Описание правила в конфигурационном файле (TOML/JSON)
+#[derive(Debug, Deserialize)]
+struct RuleConfig {
+    rules: Vec<RuleDef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleDef {
+    name: String,
+    pattern: String,
+    message: String,
+    severity: Severity,
+    languages: Vec<String>,
+}
+
+// Загружает пользовательские правила из файла, определяя формат по расширению.
+// В отличие от `TextRule::new`, компиляция regex не паникует, а сообщает об ошибке.
+fn load_rules_from_config(path: &str) -> Result<Vec<TextRule>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("не удалось прочитать файл правил '{}': {}", path, e))?;
+
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let config: RuleConfig = match ext.as_str() {
+        "json" => serde_json::from_str(&content)
+            .map_err(|e| format!("ошибка разбора JSON '{}': {}", path, e))?,
+        "toml" => toml::from_str(&content)
+            .map_err(|e| format!("ошибка разбора TOML '{}': {}", path, e))?,
+        other => return Err(format!("неподдерживаемый формат файла правил: '.{}'", other)),
+    };
+
+    let mut rules = Vec::with_capacity(config.rules.len());
+    for def in config.rules {
+        let pattern = Regex::new(&def.pattern)
+            .map_err(|e| format!("некорректное регулярное выражение в правиле '{}': {}", def.name, e))?;
+        rules.push(TextRule {
+            name: def.name,
+            pattern,
+            message: def.message,
+            severity: def.severity,
+            languages: def.languages,
+        });
+    }
+
+    Ok(rules)
+}
+
+
 
 fn main() {
     let cli  = Cli::parse();
@@ -95,7 +174,29 @@ fn main() {
         std::process::exit(1);
     }
 
-    let results: Vec<AnalysisResult> = analyze_path(&cli.path, &cli.ignore);
+    // Собираем набор правил: встроенные и/или загруженные из конфигурации
+    let mut rules: Vec<TextRule> = if cli.no_default_rules {
+        Vec::new()
+    } else {
+        get_text_rules()
+    };
+    if let Some(config_path) = &cli.config {
+        match load_rules_from_config(config_path) {
+            Ok(custom) => rules.extend(custom),
+            Err(e) => {
+                eprintln!("{}: {}", "Ошибка".red(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let results: Vec<AnalysisResult> = analyze_path(
+        &cli.path,
+        &cli.ignore,
+        cli.jobs,
+        rules,
+        cli.report_unused_suppressions,
+    );
     let filtered_results: Vec<AnalysisResult> = if cli.errors_only {
         results.into_iter()
             .filter(|r| matches!(r.severity, Severity::Error))
@@ -157,13 +258,6 @@ fn get_text_rules() -> Vec<TextRule> {
             Severity::Error,
             vec!["c", "cpp"]
         ),
-        TextRule::new(
-            "c-malloc-without-free",
-            r"malloc\s*\(",
-            "malloc без проверки на free",
-            Severity::Warning,
-            vec!["c", "cpp"]
-        ),
         TextRule::new(
             "c-printf-format",
             r"printf\s*\(",
@@ -208,7 +302,75 @@ fn get_text_rules() -> Vec<TextRule> {
 
 
 
-fn analyze_file(path: &Path, rules: &[TextRule]) -> Vec<AnalysisResult> {
+// Правило, анализирующее файл целиком, а не построчно.
+// Позволяет рассуждать о состоянии между строками (баланс malloc/free,
+// lock/unlock, open/close и т.п.).
+trait StatefulRule: Send + Sync {
+    fn analyze(&self, content: &str, ext: &str) -> Vec<AnalysisResult>;
+}
+
+// Проверка баланса выделений и освобождений памяти в C/C++.
+// Предупреждение выдаётся только когда в файле выделений больше, чем вызовов free.
+struct MallocFreeBalance {
+    alloc: Regex,
+    free: Regex,
+}
+
+impl MallocFreeBalance {
+    fn new() -> Self {
+        Self {
+            alloc: Regex::new(r"\b(?:malloc|calloc|realloc)\s*\(").unwrap(),
+            free: Regex::new(r"\bfree\s*\(").unwrap(),
+        }
+    }
+}
+
+impl StatefulRule for MallocFreeBalance {
+    fn analyze(&self, content: &str, ext: &str) -> Vec<AnalysisResult> {
+        if !matches_language(ext, "c") && !matches_language(ext, "cpp") {
+            return Vec::new();
+        }
+
+        let mut alloc_sites: Vec<(usize, String)> = Vec::new();
+        let mut free_count = 0usize;
+
+        for (line_num, line) in content.lines().enumerate() {
+            alloc_sites.extend(
+                std::iter::repeat((line_num + 1, line.trim().to_string()))
+                    .take(self.alloc.find_iter(line).count()),
+            );
+            free_count += self.free.find_iter(line).count();
+        }
+
+        // Баланс соблюдён — утечки нет
+        if alloc_sites.len() <= free_count {
+            return Vec::new();
+        }
+
+        alloc_sites
+            .into_iter()
+            .map(|(line, snippet)| AnalysisResult {
+                file: String::new(),
+                line,
+                message: "Возможная утечка памяти: выделений больше, чем вызовов free".to_string(),
+                severity: Severity::Warning,
+                rule_name: "c-malloc-without-free".to_string(),
+                code_snippet: snippet,
+            })
+            .collect()
+    }
+}
+
+fn get_stateful_rules() -> Vec<Box<dyn StatefulRule>> {
+    vec![Box::new(MallocFreeBalance::new())]
+}
+
+fn analyze_file(
+    path: &Path,
+    rules: &[TextRule],
+    stateful_rules: &[Box<dyn StatefulRule>],
+    report_unused: bool,
+) -> Vec<AnalysisResult> {
     let content = match fs::read_to_string(path) {
         Ok(c) => c,
         Err(_) => return Vec::new(),
@@ -219,25 +381,66 @@ fn analyze_file(path: &Path, rules: &[TextRule]) -> Vec<AnalysisResult> {
         .unwrap_or("")
         .to_lowercase();
 
+    if matches!(extension.as_str(), "md" | "markdown") {
+        return analyze_markdown(path, &content, rules, report_unused);
+    }
+
+    // Построчные regex-правила
+    let mut results = analyze_source(path, &content, &extension, rules, 0, report_unused);
+
+    // Правила с анализом на уровне всего файла
+    for rule in stateful_rules {
+        for mut result in rule.analyze(&content, &extension) {
+            result.file = path.display().to_string();
+            results.push(result);
+        }
+    }
+
+    results
+}
+
+// Прогоняет правила по исходному коду. `line_offset` сдвигает номера строк так,
+// чтобы они указывали на позицию в содержащем файле (0 для обычных файлов).
+fn analyze_source(
+    path: &Path,
+    content: &str,
+    extension: &str,
+    rules: &[TextRule],
+    line_offset: usize,
+    report_unused: bool,
+) -> Vec<AnalysisResult> {
     let mut results = Vec::new();
 
-    for (line_num, line) in content.lines().enumerate() {
-        let line = line.trim();
+    let lines: Vec<&str> = content.lines().collect();
+    // Директивы подавления `analyze:allow(...)`, распарсенные по каждой строке
+    let allow_re = Regex::new(r"analyze:allow\(([^)]*)\)").unwrap();
+    let directives: Vec<Option<Vec<String>>> =
+        lines.iter().map(|line| parse_allow_directive(&allow_re, line)).collect();
+    let mut used = vec![false; lines.len()];
+
+    for (line_num, raw) in lines.iter().enumerate() {
+        let line = raw.trim();
         if line.is_empty() {
             continue;
         }
 
         // Пропускаем строки, которые являются комментариями
-        if is_comment_line(line, &extension) {
+        if is_comment_line(line, extension) {
             continue;
         }
 
         for rule in rules {
-            if rule.languages.iter().any(|lang| matches_language(&extension, lang)) {
+            if rule.languages.iter().any(|lang| matches_language(extension, lang)) {
                 if rule.pattern.is_match(line) && !is_false_positive(line, &rule.name) {
+                    // Подавление: директива на самой строке или на предыдущей непустой
+                    if let Some(idx) = suppressing_directive(&lines, &directives, line_num, &rule.name) {
+                        used[idx] = true;
+                        continue;
+                    }
+
                     results.push(AnalysisResult {
                         file: path.display().to_string(),
-                        line: line_num + 1,
+                        line: line_offset + line_num + 1,
                         message: rule.message.clone(),
                         severity: rule.severity.clone(),
                         rule_name: rule.name.clone(),
@@ -248,9 +451,148 @@ fn analyze_file(path: &Path, rules: &[TextRule]) -> Vec<AnalysisResult> {
         }
     }
 
+    // Сообщаем о директивах подавления, которые ничего не подавили
+    if report_unused {
+        for (idx, directive) in directives.iter().enumerate() {
+            if directive.is_some() && !used[idx] {
+                results.push(AnalysisResult {
+                    file: path.display().to_string(),
+                    line: line_offset + idx + 1,
+                    message: "Неиспользуемая директива подавления analyze:allow".to_string(),
+                    severity: Severity::Info,
+                    rule_name: "unused-suppression".to_string(),
+                    code_snippet: lines[idx].trim().to_string(),
+                });
+            }
+        }
+    }
+
     results
 }
 
+// Извлекает имена правил из директивы `analyze:allow(a, b)` (если она есть в строке)
+fn parse_allow_directive(re: &Regex, line: &str) -> Option<Vec<String>> {
+    re.captures(line).map(|caps| {
+        caps[1]
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    })
+}
+
+fn directive_allows(directive: &Option<Vec<String>>, rule_name: &str) -> bool {
+    match directive {
+        Some(names) => names.iter().any(|name| name == rule_name || name == "all"),
+        None => false,
+    }
+}
+
+// Возвращает индекс строки с директивой, подавляющей правило: текущей строки
+// либо непосредственно предшествующей непустой строки.
+fn suppressing_directive(
+    lines: &[&str],
+    directives: &[Option<Vec<String>>],
+    line_num: usize,
+    rule_name: &str,
+) -> Option<usize> {
+    if directive_allows(&directives[line_num], rule_name) {
+        return Some(line_num);
+    }
+
+    let mut idx = line_num;
+    while idx > 0 {
+        idx -= 1;
+        if !lines[idx].trim().is_empty() {
+            if directive_allows(&directives[idx], rule_name) {
+                return Some(idx);
+            }
+            break;
+        }
+    }
+
+    None
+}
+
+// Извлекает огороженные блоки кода из Markdown и анализирует те из них,
+// чей info-string называет поддерживаемый язык (rust/c/cpp).
+fn analyze_markdown(
+    path: &Path,
+    content: &str,
+    rules: &[TextRule],
+    report_unused: bool,
+) -> Vec<AnalysisResult> {
+    let mut results = Vec::new();
+
+    let mut fence: Option<&'static str> = None;
+    let mut block_ext: Option<&'static str> = None;
+    let mut block_body = String::new();
+    let mut block_offset = 0usize;
+
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+
+        match fence {
+            None => {
+                let marker = if trimmed.starts_with("```") {
+                    Some("```")
+                } else if trimmed.starts_with("~~~") {
+                    Some("~~~")
+                } else {
+                    None
+                };
+
+                if let Some(marker) = marker {
+                    // info-string следует сразу за маркером ограждения
+                    let info = trimmed[marker.len()..].trim();
+                    let lang = info
+                        .split(|c: char| c == ' ' || c == ',')
+                        .next()
+                        .unwrap_or("")
+                        .to_lowercase();
+                    fence = Some(marker);
+                    block_ext = markdown_lang_to_ext(&lang);
+                    block_body.clear();
+                    // Номер строки открывающего ограждения (1-based)
+                    block_offset = idx + 1;
+                }
+            }
+            Some(marker) => {
+                if trimmed.starts_with(marker) {
+                    // Закрывающее ограждение: анализируем накопленное тело блока
+                    if let Some(ext) = block_ext {
+                        results.extend(analyze_source(
+                            path,
+                            &block_body,
+                            ext,
+                            rules,
+                            block_offset,
+                            report_unused,
+                        ));
+                    }
+                    fence = None;
+                    block_ext = None;
+                } else {
+                    block_body.push_str(line);
+                    block_body.push('\n');
+                }
+            }
+        }
+    }
+
+    results
+}
+
+// Сопоставляет info-string Markdown-блока с расширением поддерживаемого языка
+fn markdown_lang_to_ext(lang: &str) -> Option<&'static str> {
+    match lang {
+        "rust" => Some("rs"),
+        "c" => Some("c"),
+        "cpp" => Some("cpp"),
+        _ => None,
+    }
+}
+
 // Проверяет, является ли строка комментарием
 fn is_comment_line(line: &str, extension: &str) -> bool {
     match extension {
@@ -282,29 +624,86 @@ fn is_false_positive(line: &str, rule_name: &str) -> bool {
     false
 }
 
-fn analyze_path(path: &str, ignore_patterns: &[String]) -> Vec<AnalysisResult> {
-    let mut results = Vec::new();
-    let rules = get_text_rules();
-
-    for entry in WalkDir::new(path) {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-
-        if entry.file_type().is_file() {
-            let path = entry.path();
-            
-            // Проверка игнорируемых паттернов
-            if should_ignore(path, ignore_patterns) {
-                continue;
+fn analyze_path(
+    path: &str,
+    ignore_patterns: &[String],
+    jobs: Option<usize>,
+    rules: Vec<TextRule>,
+    report_unused: bool,
+) -> Vec<AnalysisResult> {
+    let rules = Arc::new(rules);
+    let stateful_rules = Arc::new(get_stateful_rules());
+    let ignore = build_ignore_patterns(path, ignore_patterns);
+
+    // Сначала собираем список файлов, подлежащих анализу
+    let files: Vec<std::path::PathBuf> = WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|p| !should_ignore(p, &ignore))
+        .collect();
+
+    // Количество воркеров: флаг --jobs либо доступный параллелизм
+    let worker_count = jobs
+        .filter(|n| *n > 0)
+        .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .min(files.len().max(1));
+
+    // Раздаём пути воркерам через канал, результаты собираем обратно через mpsc
+    let (task_tx, task_rx) = mpsc::channel::<std::path::PathBuf>();
+    let task_rx = Arc::new(std::sync::Mutex::new(task_rx));
+    let (result_tx, result_rx) = mpsc::channel::<Vec<AnalysisResult>>();
+
+    let mut handles = Vec::with_capacity(worker_count);
+    for _ in 0..worker_count {
+        let task_rx = Arc::clone(&task_rx);
+        let result_tx = result_tx.clone();
+        let rules = Arc::clone(&rules);
+        let stateful_rules = Arc::clone(&stateful_rules);
+        handles.push(thread::spawn(move || loop {
+            // Берём следующий путь из общей очереди
+            let next = {
+                let rx = task_rx.lock().unwrap();
+                rx.recv()
+            };
+            match next {
+                Ok(file) => {
+                    let file_results =
+                        analyze_file(&file, &rules, &stateful_rules, report_unused);
+                    // Ошибка отправки означает, что главный поток завершился
+                    if result_tx.send(file_results).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => break,
             }
-            
-            let file_results = analyze_file(path, &rules);
-            results.extend(file_results);
+        }));
+    }
+    drop(result_tx);
+    drop(task_rx);
+
+    for file in files {
+        // Отправка может завершиться ошибкой только если все воркеры умерли
+        if task_tx.send(file).is_err() {
+            break;
         }
     }
+    drop(task_tx);
+
+    // Сливаем результаты по мере завершения работы воркерами
+    let mut results: Vec<AnalysisResult> = Vec::new();
+    for batch in result_rx {
+        results.extend(batch);
+    }
+
+    for handle in handles {
+        let _ = handle.join();
+    }
 
+    // Детерминированный порядок вывода независимо от порядка завершения воркеров
+    results.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
     results
 }
 
@@ -321,6 +720,7 @@ fn print_results(results: &[AnalysisResult], format: &OutputFormat) {
     match format {
         OutputFormat::Text => print_text_results(results),
         OutputFormat::Compact => print_compact_results(results),
+        OutputFormat::Sarif => print_sarif_results(results),
     }
 }
 
@@ -354,6 +754,139 @@ fn print_text_results(results: &[AnalysisResult]) {
 }
 
 
+// --- SARIF 2.1.0 сериализация ---
+
+#[derive(Serialize)]
+struct SarifLog {
+    version: &'static str,
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Serialize)]
+struct SarifRun {
+    tool: SarifTool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize)]
+struct SarifRule {
+    id: String,
+    #[serde(rename = "shortDescription")]
+    short_description: SarifText,
+}
+
+#[derive(Serialize)]
+struct SarifResult {
+    #[serde(rename = "ruleId")]
+    rule_id: String,
+    level: &'static str,
+    message: SarifText,
+    locations: Vec<SarifLocation>,
+}
+
+#[derive(Serialize)]
+struct SarifText {
+    text: String,
+}
+
+#[derive(Serialize)]
+struct SarifLocation {
+    #[serde(rename = "physicalLocation")]
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Serialize)]
+struct SarifPhysicalLocation {
+    #[serde(rename = "artifactLocation")]
+    artifact_location: SarifArtifactLocation,
+    region: SarifRegion,
+}
+
+#[derive(Serialize)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+struct SarifRegion {
+    #[serde(rename = "startLine")]
+    start_line: usize,
+    snippet: SarifText,
+}
+
+fn print_sarif_results(results: &[AnalysisResult]) {
+    // Собираем уникальные правила, встреченные в результатах
+    let mut rules: Vec<SarifRule> = Vec::new();
+    for result in results {
+        if !rules.iter().any(|r| r.id == result.rule_name) {
+            rules.push(SarifRule {
+                id: result.rule_name.clone(),
+                short_description: SarifText {
+                    text: result.message.clone(),
+                },
+            });
+        }
+    }
+
+    let sarif_results = results
+        .iter()
+        .map(|result| SarifResult {
+            rule_id: result.rule_name.clone(),
+            level: result.severity.sarif_level(),
+            message: SarifText {
+                text: result.message.clone(),
+            },
+            locations: vec![SarifLocation {
+                physical_location: SarifPhysicalLocation {
+                    artifact_location: SarifArtifactLocation {
+                        uri: result.file.clone(),
+                    },
+                    region: SarifRegion {
+                        start_line: result.line,
+                        snippet: SarifText {
+                            text: result.code_snippet.clone(),
+                        },
+                    },
+                },
+            }],
+        })
+        .collect();
+
+    let log = SarifLog {
+        version: "2.1.0",
+        schema: "https://json.schemastore.org/sarif-2.1.0.json",
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "code-analyze",
+                    version: "1.0",
+                    rules,
+                },
+            },
+            results: sarif_results,
+        }],
+    };
+
+    match serde_json::to_string_pretty(&log) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("{}: не удалось сериализовать SARIF: {}", "Ошибка".red(), e),
+    }
+}
+
 fn print_compact_results(results: &[AnalysisResult]) {
     for result in results {
         let severity_char = match result.severity {
@@ -365,23 +898,107 @@ fn print_compact_results(results: &[AnalysisResult]) {
     }
 }
 
-fn should_ignore(path: &Path, ignore_patterns: &[String]) -> bool {
-    let path_str = path.to_string_lossy();
-    
-    // Автоматически игнорируем системные папки
-    if path_str.contains("/target/") || 
-       path_str.contains("/.git/") || 
-       path_str.contains("/node_modules/") ||
-       path_str.contains("/build/") {
-        return true;
+// Скомпилированный ignore-паттерн в стиле .gitignore
+#[derive(Debug)]
+struct IgnorePattern {
+    regex: Regex,
+    negation: bool,
+}
+
+// Переводит glob-паттерн (как в .gitignore) в регулярное выражение
+fn glob_to_regex(pattern: &str) -> Option<IgnorePattern> {
+    let mut pat = pattern;
+
+    // Ведущий `!` помечает паттерн как отрицание (повторное включение)
+    let negation = pat.starts_with('!');
+    if negation {
+        pat = &pat[1..];
     }
-    
-    // Проверяем пользовательские паттерны
-    for pattern in ignore_patterns {
-        if path_str.contains(pattern) {
-            return true;
+
+    // Ведущий `/` привязывает паттерн к корню пути
+    let anchored = pat.starts_with('/');
+    if anchored {
+        pat = &pat[1..];
+    }
+
+    // Завершающий `/` означает "только каталог"
+    let dir_only = pat.ends_with('/');
+    if dir_only {
+        pat = &pat[..pat.len() - 1];
+    }
+
+    let chars: Vec<char> = pat.chars().collect();
+    let mut body = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                if i + 1 < chars.len() && chars[i + 1] == '*' {
+                    body.push_str(".*");
+                    i += 1;
+                } else {
+                    body.push_str("[^/]*");
+                }
+            }
+            '?' => body.push_str("[^/]"),
+            // Экранируем метасимволы регулярных выражений
+            c @ ('.' | '+' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' | '\\') => {
+                body.push('\\');
+                body.push(c);
+            }
+            c => body.push(c),
         }
+        i += 1;
     }
-    
-    false
+
+    let start = if anchored { "^" } else { "(^|/)" };
+    let end = if dir_only { "/" } else { "($|/)" };
+    let full = format!("{}{}{}", start, body, end);
+
+    Regex::new(&full).ok().map(|regex| IgnorePattern { regex, negation })
+}
+
+// Читает `.analyzeignore` из корня сканирования, пропуская пустые строки и комментарии
+fn load_analyzeignore(root: &str) -> Vec<String> {
+    let ignore_file = Path::new(root).join(".analyzeignore");
+    let content = match fs::read_to_string(&ignore_file) {
+        Ok(c) => c,
+        Err(_) => return Vec::new(),
+    };
+
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+// Собирает итоговый список паттернов: сначала из `.analyzeignore`, затем из CLI
+fn build_ignore_patterns(root: &str, cli_patterns: &[String]) -> Vec<IgnorePattern> {
+    let mut raw = load_analyzeignore(root);
+    raw.extend(cli_patterns.iter().cloned());
+
+    raw.iter().filter_map(|p| glob_to_regex(p)).collect()
+}
+
+fn should_ignore(path: &Path, patterns: &[IgnorePattern]) -> bool {
+    let path_str = path.to_string_lossy();
+
+    // Паттерны применяются по порядку: более позднее отрицание переопределяет ранний запрет
+    let mut ignored: Option<bool> = None;
+    for pattern in patterns {
+        if pattern.regex.is_match(&path_str) {
+            ignored = Some(!pattern.negation);
+        }
+    }
+    if let Some(decision) = ignored {
+        return decision;
+    }
+
+    // Если ни один пользовательский паттерн не совпал — системные папки по умолчанию
+    path_str.contains("/target/")
+        || path_str.contains("/.git/")
+        || path_str.contains("/node_modules/")
+        || path_str.contains("/build/")
 }